@@ -1,5 +1,5 @@
-#![crate_name = "union_find"]
-#![crate_type="lib"]
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
 /**
 Also known as the Disjoint-Set data structure.
@@ -10,16 +10,16 @@ Creating a set:
 
  ```rust
  use union_find::UnionFind;
-// Create a `UnionFind` node. In order to be useful, a node must be mutable.
-let mut x = UnionFind::make_set(1u);
-let (mut y, mut z) = (UnionFind::make_set(1u), UnionFind::make_set(2u));
-// Exploring behaivor.
-assert!(x.value == 1u);
-assert!(x.value == y.value);
-assert!(x == y); // Gotcha! Use pointers if you need uniques.
-assert!(x.value != z.value);
-assert!(x != z);
-assert!(x.parent == None);
+ // Create a `UnionFind` node. In order to be useful, a node must be mutable.
+ let mut x = UnionFind::make_set(1u32);
+ let (mut y, mut z) = (UnionFind::make_set(1u32), UnionFind::make_set(2u32));
+ // Exploring behaivor.
+ assert!(x.value == 1u32);
+ assert!(x.value == y.value);
+ assert!(x == y); // Gotcha! Use pointers if you need uniques.
+ assert!(x.value != z.value);
+ assert!(x != z);
+ assert!(x.parent == None);
  ```
 
  Union two sets:
@@ -46,15 +46,15 @@ assert!(x.parent == None);
   UnionFind::make_set("Foo"),
   UnionFind::make_set("Bar"),
   UnionFind::make_set("Baz"));
-x.clone().union(&mut y);
-// Check relationships.
-assert!(y.clone().find() == x);
-assert!(y.clone().find() == x.clone().find());
-assert!(y.find() != z.find());
+ x.clone().union(&mut y);
+ // Check relationships.
+ assert!(y.clone().find() == x);
+ assert!(y.clone().find() == x.clone().find());
+ assert!(y.find() != z.find());
  ```
 
  */
-#[deriving(Clone, PartialEq, Show)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct UnionFind<T> {
     /** The value of the node. */
     pub value: T,
@@ -69,7 +69,7 @@ pub struct UnionFind<T> {
 impl<T> UnionFind<T> {
     /**  Encapsulates a `value` into a `UnionFind` node. It's parent is set to `None`, meaning it's a canonical node. */
     pub fn make_set(value: T) -> UnionFind<T> {
-        UnionFind { value: value, parent: None }
+        UnionFind { value, parent: None }
     }
 
     // There's no reason to do path compression when you can just forget about one of the values :P
@@ -82,15 +82,261 @@ impl<T> UnionFind<T> {
 
     /** Union two `UnionFind` data structures together. */
     pub fn union(self, other: &mut UnionFind<T>) {
-        other.parent = Some(box self);
+        other.parent = Some(Box::new(self));
+    }
+}
+
+/**
+An index-based Union-Find backend over `0..n` integer keys.
+
+Backed by two flat `Vec`s: `parent` (each element initially points to
+itself) and `rank`. `find` applies path halving as it walks to the
+root; `union` attaches the lower-rank root under the higher-rank one.
+
+Use `from_edges` to build straight from a graph's edge list and solve
+connected components in one call.
+
+```rust
+use union_find::IndexedUnionFind;
+let mut uf = IndexedUnionFind::new(5);
+uf.union(0, 1);
+uf.union(1, 2);
+assert_eq!(uf.find(0), uf.find(2));
+assert!(uf.find(0) != uf.find(3));
+```
+*/
+pub struct IndexedUnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl IndexedUnionFind {
+    /** Creates a new backend over the keys `0..n`, each starting out as its own canonical set. */
+    pub fn new(n: usize) -> IndexedUnionFind {
+        IndexedUnionFind {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    /** Finds the canonical key for `x`, compressing the path to the root along the way. */
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut x = x;
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /** Unions the sets containing `a` and `b`, attaching the lower-rank root under the higher-rank one. */
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+        if self.rank[root_a] < self.rank[root_b] {
+            self.parent[root_a] = root_b;
+        } else if self.rank[root_a] > self.rank[root_b] {
+            self.parent[root_b] = root_a;
+        } else {
+            self.parent[root_b] = root_a;
+            self.rank[root_a] += 1;
+        }
+    }
+
+    /** Appends a new singleton set and returns its key. */
+    pub fn push(&mut self) -> usize {
+        let key = self.parent.len();
+        self.parent.push(key);
+        self.rank.push(0);
+        key
+    }
+
+    /** Builds a backend over `0..n` and unions each `(a, b)` pair from `edges`, solving connected components in one pass. */
+    pub fn from_edges<I: IntoIterator<Item = (usize, usize)>>(n: usize, edges: I) -> IndexedUnionFind {
+        let mut uf = IndexedUnionFind::new(n);
+        for (a, b) in edges {
+            uf.union(a, b);
+        }
+        uf
+    }
+
+    /** Counts the distinct canonical roots, i.e. the number of connected components. */
+    pub fn component_count(&mut self) -> usize {
+        let mut roots: Vec<usize> = (0..self.parent.len()).map(|x| self.find(x)).collect();
+        roots.sort();
+        roots.dedup();
+        roots.len()
+    }
+
+    /** Assigns each key a dense component id `0..component_count()`, in order of first appearance. */
+    pub fn labeling(&mut self) -> Vec<usize> {
+        let mut next_label = HashMap::new();
+        let mut labels = Vec::with_capacity(self.parent.len());
+        for x in 0..self.parent.len() {
+            let root = self.find(x);
+            let next = next_label.len();
+            let label = *next_label.entry(root).or_insert(next);
+            labels.push(label);
+        }
+        labels
+    }
+}
+
+/**
+A keyed Union-Find over arbitrary hashable values.
+
+Lets you union values like `String` or `&str` directly: it assigns each
+distinct `T` a stable integer tag on first `make_set`, and keeps an
+`IndexedUnionFind` underneath to do the actual linking.
+
+Sets may also carry arbitrary per-set `Data`, readable and mutable
+through any member via `make_set_with`/`with_data`, and reconciled on
+`union_with` by a caller-supplied merge function.
+
+```rust
+use union_find::KeyedUnionFind;
+let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+uf.make_set("red");
+uf.make_set("blue");
+uf.make_set("green");
+uf.union(&"red", &"blue");
+assert!(uf.in_same_set(&"red", &"blue"));
+assert!(!uf.in_same_set(&"red", &"green"));
+assert_eq!(uf.num_sets(), 2);
+```
+*/
+pub struct KeyedUnionFind<T, Data = ()> {
+    map: HashMap<T, usize>,
+    backend: IndexedUnionFind,
+    data: Vec<Option<Data>>,
+    num_sets: usize,
+}
+
+impl<T: Eq + Hash, Data> KeyedUnionFind<T, Data> {
+    /** Creates an empty `KeyedUnionFind` with no tracked values. */
+    pub fn new() -> KeyedUnionFind<T, Data> {
+        KeyedUnionFind {
+            map: HashMap::new(),
+            backend: IndexedUnionFind::new(0),
+            data: Vec::new(),
+            num_sets: 0,
+        }
+    }
+
+    /** Tracks `value` as its own canonical set, with no associated data. Idempotent if `value` is already tracked. */
+    pub fn make_set(&mut self, value: T) {
+        self.insert_set(value, None);
+    }
+
+    /** Tracks `value` as its own canonical set, associating `data` with it. Idempotent if `value` is already tracked. */
+    pub fn make_set_with(&mut self, value: T, data: Data) {
+        self.insert_set(value, Some(data));
+    }
+
+    fn insert_set(&mut self, value: T, data: Option<Data>) {
+        if self.map.contains_key(&value) {
+            return;
+        }
+        let tag = self.backend.push();
+        self.data.push(data);
+        self.map.insert(value, tag);
+        self.num_sets += 1;
+    }
+
+    /** Finds the integer tag of the canonical set containing `value`. */
+    pub fn find(&mut self, value: &T) -> usize {
+        let tag = self.map[value];
+        self.backend.find(tag)
+    }
+
+    /** Unions the sets containing `a` and `b`. If either side carries associated data, use `union_with` instead to reconcile it. */
+    pub fn union(&mut self, a: &T, b: &T) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+        self.backend.union(root_a, root_b);
+        self.num_sets -= 1;
+    }
+
+    /** Reads or mutates the data associated with the representative of the set containing `x`. Panics if that set was never given data via `make_set_with`. */
+    pub fn with_data<R>(&mut self, x: &T, f: impl FnOnce(&mut Data) -> R) -> R {
+        let root = self.find(x);
+        let data = self.data[root].as_mut().expect("with_data requires the set to carry data");
+        f(data)
+    }
+
+    /**
+    Unions the sets containing `a` and `b` by rank, then reconciles their
+    associated data via `merge` and stores the result on the surviving
+    root. Panics if either set was never given data via `make_set_with`.
+    */
+    pub fn union_with(&mut self, a: &T, b: &T, merge: impl FnOnce(Data, Data) -> Data) {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return;
+        }
+        let data_a = self.data[root_a].take().expect("union_with requires both sets to carry data");
+        let data_b = self.data[root_b].take().expect("union_with requires both sets to carry data");
+        self.backend.union(root_a, root_b);
+        self.num_sets -= 1;
+        let new_root = self.backend.find(root_a);
+        self.data[new_root] = Some(merge(data_a, data_b));
+    }
+
+    /** Returns whether `a` and `b` are in the same set. */
+    pub fn in_same_set(&mut self, a: &T, b: &T) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /** Returns the number of distinct sets currently tracked. */
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /**
+    Consumes the `KeyedUnionFind`, bucketing every tracked value by its
+    canonical root into one `HashSet` per disjoint partition. Pass
+    `skip_singletons` as `true` to drop sets with only one member, which
+    is handy for clustering use cases where you only care about values
+    that actually got merged with something.
+    */
+    pub fn into_subsets(self, skip_singletons: bool) -> Vec<HashSet<T>> {
+        let mut backend = self.backend;
+        let mut buckets: HashMap<usize, HashSet<T>> = HashMap::new();
+        for (value, tag) in self.map.into_iter() {
+            let root = backend.find(tag);
+            buckets.entry(root).or_default().insert(value);
+        }
+        buckets.into_values()
+            .filter(|set| !skip_singletons || set.len() > 1)
+            .collect()
+    }
+}
+
+impl<T: Eq + Hash, Data> Default for KeyedUnionFind<T, Data> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/** Iterates the disjoint partitions of a `KeyedUnionFind`, one `HashSet` per set, including singletons. */
+impl<T: Eq + Hash, Data> IntoIterator for KeyedUnionFind<T, Data> {
+    type Item = HashSet<T>;
+    type IntoIter = ::std::vec::IntoIter<HashSet<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_subsets(false).into_iter()
     }
 }
 
 #[test]
 fn can_create () {
     // Create with integer.
-    let int_node = UnionFind::make_set(1u);
-    assert_eq!(int_node.value, 1u);
+    let int_node = UnionFind::make_set(1u32);
+    assert_eq!(int_node.value, 1u32);
     // With String
     let string_node = UnionFind::make_set("Foo".to_string());
     assert_eq!(string_node.value, "Foo".to_string());
@@ -98,16 +344,16 @@ fn can_create () {
 
 #[test]
 fn can_union () {
-    let one = UnionFind::make_set(1u);
-    let mut two = UnionFind::make_set(2u);
+    let one = UnionFind::make_set(1u32);
+    let mut two = UnionFind::make_set(2u32);
     one.clone().union(&mut two);
     assert_eq!(two.find(), one);
 }
 
 #[test]
 fn can_find () {
-    let one = UnionFind::make_set(1u);
-    let mut two = UnionFind::make_set(2u);
+    let one = UnionFind::make_set(1u32);
+    let mut two = UnionFind::make_set(2u32);
     // Does it find on bare?
     assert_eq!(one.clone().find().value, one.value);
     one.clone().union(&mut two);
@@ -115,3 +361,147 @@ fn can_find () {
     assert_eq!(two.find().value, one.value);
     assert_eq!(one.clone().find().value, one.value);
 }
+
+#[test]
+fn indexed_find_is_reflexive_until_unioned () {
+    let mut uf = IndexedUnionFind::new(4);
+    assert_eq!(uf.find(0), 0);
+    assert_eq!(uf.find(3), 3);
+}
+
+#[test]
+fn indexed_union_merges_sets () {
+    let mut uf = IndexedUnionFind::new(5);
+    uf.union(0, 1);
+    uf.union(1, 2);
+    assert_eq!(uf.find(0), uf.find(2));
+    assert!(uf.find(0) != uf.find(3));
+}
+
+#[test]
+fn indexed_union_is_idempotent () {
+    let mut uf = IndexedUnionFind::new(3);
+    uf.union(0, 1);
+    let root = uf.find(0);
+    uf.union(0, 1);
+    assert_eq!(uf.find(0), root);
+}
+
+#[test]
+fn indexed_push_grows_the_backend () {
+    let mut uf = IndexedUnionFind::new(0);
+    let a = uf.push();
+    let b = uf.push();
+    assert_eq!(uf.find(a), a);
+    assert!(a != b);
+}
+
+#[test]
+fn from_edges_computes_component_count () {
+    let mut uf = IndexedUnionFind::from_edges(5, [(0, 1), (1, 2), (3, 4)]);
+    assert_eq!(uf.component_count(), 2);
+}
+
+#[test]
+fn from_edges_with_no_edges_is_all_singletons () {
+    let mut uf = IndexedUnionFind::from_edges(4, []);
+    assert_eq!(uf.component_count(), 4);
+}
+
+#[test]
+fn labeling_is_dense_and_agrees_with_components () {
+    let mut uf = IndexedUnionFind::from_edges(5, [(0, 1), (1, 2), (3, 4)]);
+    let labels = uf.labeling();
+    assert_eq!(labels[0], labels[1]);
+    assert_eq!(labels[1], labels[2]);
+    assert_eq!(labels[3], labels[4]);
+    assert!(labels[0] != labels[3]);
+    let max_label = *labels.iter().max().unwrap();
+    assert_eq!(max_label + 1, uf.component_count());
+}
+
+#[test]
+fn keyed_make_set_is_idempotent () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("red");
+    assert_eq!(uf.num_sets(), 1);
+}
+
+#[test]
+fn keyed_union_merges_and_counts_sets () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("blue");
+    uf.make_set("green");
+    assert_eq!(uf.num_sets(), 3);
+    uf.union(&"red", &"blue");
+    assert_eq!(uf.num_sets(), 2);
+    assert!(uf.in_same_set(&"red", &"blue"));
+    assert!(!uf.in_same_set(&"red", &"green"));
+}
+
+#[test]
+fn keyed_union_is_idempotent () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("blue");
+    uf.union(&"red", &"blue");
+    uf.union(&"red", &"blue");
+    assert_eq!(uf.num_sets(), 1);
+}
+
+#[test]
+fn into_subsets_buckets_by_root () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("blue");
+    uf.make_set("green");
+    uf.union(&"red", &"blue");
+    let subsets = uf.into_subsets(false);
+    assert_eq!(subsets.len(), 2);
+    assert!(subsets.iter().any(|s| s.len() == 2 && s.contains("red") && s.contains("blue")));
+    assert!(subsets.iter().any(|s| s.len() == 1 && s.contains("green")));
+}
+
+#[test]
+fn into_subsets_can_skip_singletons () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("blue");
+    uf.make_set("green");
+    uf.union(&"red", &"blue");
+    let subsets = uf.into_subsets(true);
+    assert_eq!(subsets.len(), 1);
+    assert!(subsets[0].contains("red") && subsets[0].contains("blue"));
+}
+
+#[test]
+fn into_iter_yields_all_subsets_including_singletons () {
+    let mut uf: KeyedUnionFind<&str> = KeyedUnionFind::new();
+    uf.make_set("red");
+    uf.make_set("blue");
+    uf.make_set("green");
+    uf.union(&"red", &"blue");
+    let subsets: Vec<_> = uf.into_iter().collect();
+    assert_eq!(subsets.len(), 2);
+    assert!(subsets.iter().any(|s| s.len() == 1 && s.contains("green")));
+}
+
+#[test]
+fn with_data_reads_and_mutates_in_place () {
+    let mut uf = KeyedUnionFind::new();
+    uf.make_set_with("red", 1u32);
+    uf.with_data(&"red", |data| *data += 1);
+    assert_eq!(uf.with_data(&"red", |data| *data), 2);
+}
+
+#[test]
+fn union_with_merges_data_onto_surviving_root () {
+    let mut uf = KeyedUnionFind::new();
+    uf.make_set_with("red", 1u32);
+    uf.make_set_with("blue", 2u32);
+    uf.union_with(&"red", &"blue", |a, b| a + b);
+    assert_eq!(uf.with_data(&"red", |data| *data), 3);
+    assert_eq!(uf.with_data(&"blue", |data| *data), 3);
+}